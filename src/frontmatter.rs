@@ -0,0 +1,67 @@
+//! YAML frontmatter parsing for notes.
+
+use std::collections::BTreeMap;
+
+use serde_yaml::Value;
+use snafu::{ResultExt, Snafu};
+
+#[derive(Debug, Snafu)]
+pub enum FrontmatterError {
+    #[snafu(display("failed to parse frontmatter yaml"))]
+    Parse { source: serde_yaml::Error },
+
+    #[snafu(display("failed to serialize frontmatter yaml"))]
+    Serialize { source: serde_yaml::Error },
+}
+
+type Result<T> = std::result::Result<T, FrontmatterError>;
+
+/// Controls whether a note's YAML frontmatter block is kept when the note is saved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrontmatterStrategy {
+    /// Keep the frontmatter block only if it has at least one key.
+    #[default]
+    Auto,
+    /// Always emit a frontmatter block, even if it has no keys.
+    Always,
+    /// Never emit a frontmatter block, stripping one if present.
+    Never,
+}
+
+/// Parsed YAML frontmatter of a [`Note`](crate::Note).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Frontmatter {
+    keys: BTreeMap<String, Value>,
+}
+
+impl Frontmatter {
+    /// Parses a frontmatter block from the raw YAML found between the `---` delimiters.
+    pub(crate) fn parse(raw: &str) -> Result<Self> {
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+
+        let keys = serde_yaml::from_str(raw).context(ParseSnafu)?;
+        Ok(Self { keys })
+    }
+
+    /// Serializes the frontmatter back to YAML.
+    pub(crate) fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(&self.keys).context(SerializeSnafu)
+    }
+
+    /// Returns `true` if the frontmatter has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Gets the value associated with `key`, if any.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.keys.get(key)
+    }
+
+    /// Sets `key` to `value`, overwriting any previous value.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<Value>) {
+        self.keys.insert(key.into(), value.into());
+    }
+}
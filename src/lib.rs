@@ -1,12 +1,17 @@
 //! Opinionated library to parse and interact with an obsidian.md vault.
 #![warn(missing_docs)]
 
+mod frontmatter;
 pub mod markdown;
 mod note;
+mod postprocess;
 mod section;
 mod vault;
 
+pub use frontmatter::{Frontmatter, FrontmatterStrategy};
 pub use note::Note;
+pub use postprocess::{PostprocessContext, PostprocessResult};
+pub use section::ChecklistItem;
 pub use vault::Vault;
 
 // Re-exports
@@ -1,5 +1,7 @@
 //! Utilities to manipulate obsidian-markdown structures.
 
+use regex::Regex;
+
 /// Types implementing `ToMarkdown` can be converted to a markdown string.
 pub trait ToMarkdown {
     /// Serializes the object to markdown.
@@ -78,6 +80,50 @@ impl<T: AsRef<str>> ToMarkdown for LocalLink<T> {
     }
 }
 
+/// A parsed Obsidian link or embed, as found by [`Note::links`](crate::Note::links).
+///
+/// e.g. `[[Some Page#Heading|Alias]]` or `![[Some Page]]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedLink {
+    /// The linked file's name.
+    pub file: String,
+    /// An optional `#heading` or `#^block` anchor.
+    pub block: Option<String>,
+    /// An optional `|display` alias.
+    pub label: Option<String>,
+    /// Whether this is an embed (`![[...]]`) rather than a plain link.
+    pub embed: bool,
+}
+
+impl ParsedLink {
+    /// Scans `text` for every `[[...]]` / `![[...]]` occurrence and parses it.
+    pub(crate) fn scan(text: &str) -> Vec<Self> {
+        let outer = Regex::new(r"(?P<embed>!)?\[\[(?P<inner>[^\[\]]+)\]\]").unwrap();
+
+        outer
+            .captures_iter(text)
+            .filter_map(|caps| {
+                let inner = caps.name("inner")?.as_str();
+                Self::parse(caps.name("embed").is_some(), inner)
+            })
+            .collect()
+    }
+
+    /// Parses the inner text of a single `[[...]]` (i.e. without the brackets).
+    pub(crate) fn parse(embed: bool, inner: &str) -> Option<Self> {
+        let inner_pat =
+            Regex::new(r"^(?P<file>[^#|]+)(#(?P<block>.+?))??(\|(?P<label>.+?))??$").unwrap();
+        let parsed = inner_pat.captures(inner)?;
+
+        Some(Self {
+            file: parsed.name("file")?.as_str().to_string(),
+            block: parsed.name("block").map(|m| m.as_str().to_string()),
+            label: parsed.name("label").map(|m| m.as_str().to_string()),
+            embed,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +147,46 @@ mod tests {
         let lnk = LocalLink::new("Some Page");
         assert_eq!(lnk.to_markdown(), "[[Some Page]]");
     }
+
+    #[test]
+    fn parse_plain_link() {
+        let links = ParsedLink::scan("See [[Some Page]] for details.");
+        assert_eq!(
+            links,
+            vec![ParsedLink {
+                file: "Some Page".to_string(),
+                block: None,
+                label: None,
+                embed: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_link_with_heading_and_alias() {
+        let links = ParsedLink::scan("[[Some Page#Heading|Alias]]");
+        assert_eq!(
+            links,
+            vec![ParsedLink {
+                file: "Some Page".to_string(),
+                block: Some("Heading".to_string()),
+                label: Some("Alias".to_string()),
+                embed: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_embed() {
+        let links = ParsedLink::scan("![[Some Page#^abc123]]");
+        assert_eq!(
+            links,
+            vec![ParsedLink {
+                file: "Some Page".to_string(),
+                block: Some("^abc123".to_string()),
+                label: None,
+                embed: true,
+            }]
+        );
+    }
 }
@@ -1,13 +1,17 @@
-use std::cell::RefCell;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::hash_map::DefaultHasher;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use snafu::{ResultExt, Snafu};
+use snafu::{ensure, ResultExt, Snafu};
 
 use xi_rope::tree::Node;
 use xi_rope::{Rope, RopeInfo};
 
+use crate::frontmatter::{Frontmatter, FrontmatterStrategy};
 use crate::markdown::ToMarkdown;
 use crate::section::Section;
 
@@ -19,46 +23,192 @@ pub enum NoteError {
     #[snafu(display("failed to save note: {:?}", path))]
     Save { source: io::Error, path: PathBuf },
 
+    #[snafu(display("note {:?} was modified on disk since it was opened", path))]
+    Conflict { path: PathBuf },
+
     #[snafu(display("section '{}' not found", section))]
     SectionNotFound { section: String },
+
+    #[snafu(display("failed to process frontmatter of note {:?}", path))]
+    Frontmatter {
+        source: crate::frontmatter::FrontmatterError,
+        path: PathBuf,
+    },
 }
 
 type Result<T> = std::result::Result<T, NoteError>;
 
+/// A cheap fingerprint of a note's on-disk contents, used to detect external
+/// modifications between `open` and `save`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    mtime: Option<SystemTime>,
+    hash: u64,
+}
+
+impl Fingerprint {
+    fn compute(contents: &str, path: &Path) -> Self {
+        let mtime = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+
+        let mut hasher = DefaultHasher::new();
+        contents.hash(&mut hasher);
+
+        Self {
+            mtime,
+            hash: hasher.finish(),
+        }
+    }
+}
+
 /// Low-level structure wrapping a markdown note.
 pub struct Note {
     rope: RefCell<Node<RopeInfo>>,
     path: PathBuf,
+    frontmatter: RefCell<Frontmatter>,
+    frontmatter_strategy: Cell<FrontmatterStrategy>,
+    body_start: Cell<usize>,
+    fingerprint: Cell<Fingerprint>,
 }
 
 impl Note {
     pub(crate) fn open(path: PathBuf) -> Result<Self> {
         let contents =
             fs::read_to_string(&path).with_context(|_| OpenSnafu { path: path.clone() })?;
-        let rope = Rope::from(contents);
+        let fingerprint = Fingerprint::compute(&contents, &path);
 
-        // TODO: Parse YAML frontmatter and extract the metadata here.
+        let (frontmatter, body_start) = match split_frontmatter(&contents) {
+            Some((raw, body_start)) => (
+                Frontmatter::parse(raw).context(FrontmatterSnafu { path: path.clone() })?,
+                body_start,
+            ),
+            None => (Frontmatter::default(), 0),
+        };
+
+        let rope = Rope::from(contents);
 
         Ok(Self {
             rope: RefCell::new(rope),
             path,
+            frontmatter: RefCell::new(frontmatter),
+            frontmatter_strategy: Cell::new(FrontmatterStrategy::default()),
+            body_start: Cell::new(body_start),
+            fingerprint: Cell::new(fingerprint),
         })
     }
 
+    /// The path this note was opened from.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the note's parsed YAML frontmatter.
+    pub fn frontmatter(&self) -> Ref<'_, Frontmatter> {
+        self.frontmatter.borrow()
+    }
+
+    /// Sets a key in the note's frontmatter, re-serializing the block in place.
+    ///
+    /// # Errors
+    /// Returns an error if the frontmatter can't be serialized back to YAML.
+    pub fn set_frontmatter_key(
+        &self,
+        key: impl Into<String>,
+        value: impl Into<serde_yaml::Value>,
+    ) -> Result<()> {
+        self.frontmatter.borrow_mut().set(key, value);
+        self.sync_frontmatter()
+    }
+
+    /// Sets the strategy used to decide whether the frontmatter block is kept, and
+    /// re-syncs the block in place to reflect it immediately.
+    ///
+    /// # Errors
+    /// Returns an error if the frontmatter can't be serialized back to YAML.
+    pub fn set_frontmatter_strategy(&self, strategy: FrontmatterStrategy) -> Result<()> {
+        self.frontmatter_strategy.set(strategy);
+        self.sync_frontmatter()
+    }
+
+    /// Re-serializes the frontmatter (per the current strategy) and splices it back
+    /// into the rope, leaving the body untouched.
+    fn sync_frontmatter(&self) -> Result<()> {
+        let frontmatter = self.frontmatter.borrow();
+        let keep = match self.frontmatter_strategy.get() {
+            FrontmatterStrategy::Never => false,
+            FrontmatterStrategy::Always => true,
+            FrontmatterStrategy::Auto => !frontmatter.is_empty(),
+        };
+
+        let block = if keep {
+            let yaml = frontmatter
+                .to_yaml()
+                .context(FrontmatterSnafu { path: self.path.clone() })?;
+            format!("---\n{yaml}---\n")
+        } else {
+            String::new()
+        };
+        drop(frontmatter);
+
+        let mut rope = self.rope.borrow_mut();
+        let new_block = Rope::from(block);
+        let new_body_start = new_block.len();
+        rope.edit(0..self.body_start.get(), new_block);
+        self.body_start.set(new_body_start);
+
+        Ok(())
+    }
+
     /// Saves pending changes to disk.
     ///
-    /// Note: As of the time of writing, `save()` does not check whether the underlying
-    ///       file was changed by an external program, making accidental overwrites possible.
+    /// This writes the note directly; it does not run a [`Vault`](crate::Vault)'s
+    /// registered postprocessors, which run during `Vault::export` or an explicit
+    /// `Vault::postprocess` call.
+    ///
+    /// # Errors
+    /// Returns `NoteError::Conflict` if the file was changed on disk since it was
+    /// opened (or last saved here), to avoid silently clobbering it. Use
+    /// `save_force` to overwrite regardless.
     pub fn save(&self) -> Result<()> {
-        // FIXME(0.1): We should validate that the contents of the note didn't change since it was opened.
-        //             If it did, abort saving. (maybe add a `force` parameter to force saving?).
-        fs::write(&self.path, &self.rope.borrow().to_string()).context(SaveSnafu {
+        self.save_impl(false)
+    }
+
+    /// Saves pending changes to disk, bypassing the external-modification check.
+    ///
+    /// # Errors
+    /// Returns an error if the note can't be written.
+    pub fn save_force(&self) -> Result<()> {
+        self.save_impl(true)
+    }
+
+    fn save_impl(&self, force: bool) -> Result<()> {
+        // `sync_frontmatter` already runs eagerly whenever the frontmatter or its
+        // strategy is changed, so the rope is already current here. Re-running it on
+        // every save would re-serialize (and alphabetize) the YAML block even when
+        // only the body changed.
+        if !force {
+            let on_disk = fs::read_to_string(&self.path).context(SaveSnafu {
+                path: self.path.clone(),
+            })?;
+            ensure!(
+                Fingerprint::compute(&on_disk, &self.path) == self.fingerprint.get(),
+                ConflictSnafu {
+                    path: self.path.clone()
+                }
+            );
+        }
+
+        let contents = self.rope.borrow().to_string();
+        fs::write(&self.path, &contents).context(SaveSnafu {
             path: self.path.clone(),
-        })
+        })?;
+        self.fingerprint
+            .set(Fingerprint::compute(&contents, &self.path));
+
+        Ok(())
     }
 
     fn section<'a>(&'a self, name: Option<&str>) -> Option<Section<'a>> {
-        let root_section: Section<'a> = Section::new(0, .., &self.rope);
+        let root_section: Section<'a> = Section::new(0, self.body_start.get().., &self.rope);
         match name {
             Some(s) => root_section.subsection(s),
             None => Some(root_section),
@@ -98,6 +248,59 @@ impl Note {
         Ok(())
     }
 
+    /// Extracts all Obsidian links and embeds referenced in the note.
+    ///
+    /// Passing the `section` parameter will only scan that section.
+    ///
+    /// # Errors
+    /// Returns an error if a section name is specified and that section is not found.
+    pub fn links(&self, section: Option<&str>) -> Result<Vec<crate::markdown::ParsedLink>> {
+        let section = self
+            .section(section)
+            .ok_or_else(|| NoteError::SectionNotFound {
+                section: String::from(section.unwrap()),
+            })?;
+        Ok(section.links())
+    }
+
+    /// Scans the note for `- [ ]` / `- [x]` checklist items.
+    ///
+    /// Passing the `section` parameter will only scan that section.
+    ///
+    /// # Errors
+    /// Returns an error if a section name is specified and that section is not found.
+    pub fn checklist_items(
+        &self,
+        section: Option<&str>,
+    ) -> Result<Vec<crate::section::ChecklistItem>> {
+        let section = self
+            .section(section)
+            .ok_or_else(|| NoteError::SectionNotFound {
+                section: String::from(section.unwrap()),
+            })?;
+        Ok(section.checklist_items())
+    }
+
+    /// Flips a checklist item's checked state in place.
+    ///
+    /// `item` must have come from this note's own [`checklist_items`](Self::checklist_items);
+    /// its edit location was already resolved to an absolute offset when it was scanned,
+    /// so there's no separate section to target here.
+    pub fn toggle(&self, item: &crate::section::ChecklistItem) {
+        self.set_checked(item, !item.checked());
+    }
+
+    /// Sets a checklist item's checked state in place.
+    ///
+    /// `item` must have come from this note's own [`checklist_items`](Self::checklist_items);
+    /// its edit location was already resolved to an absolute offset when it was scanned,
+    /// so there's no separate section to target here.
+    pub fn set_checked(&self, item: &crate::section::ChecklistItem, checked: bool) {
+        // `section(None)` always returns the root section, spanning the whole body.
+        let mut section = self.section(None).unwrap();
+        section.set_checked(item, checked);
+    }
+
     /// Trims whitespace at the end of a note.
     ///
     /// Passing the `section` parameter will trim whitespace from the end of the section.
@@ -122,3 +325,144 @@ impl ToString for Note {
         self.rope.borrow().to_string()
     }
 }
+
+/// Splits a leading `---\n...\n---` YAML frontmatter block off `contents`, returning
+/// the raw YAML and the byte offset where the body starts. Returns `None` if `contents`
+/// doesn't open with a frontmatter delimiter, or if no line consisting of just `---`
+/// closes it.
+fn split_frontmatter(contents: &str) -> Option<(&str, usize)> {
+    contents.strip_prefix("---\n")?;
+    // Search from the opening fence's own newline, so that a closing fence right
+    // after it (an empty block) is found too.
+    let after_open = &contents[3..];
+
+    let delim = "\n---";
+    let mut search_from = 0;
+    let yaml_end = loop {
+        let candidate = search_from + after_open[search_from..].find(delim)?;
+        let after_dashes = &after_open[candidate + delim.len()..];
+
+        // The closing fence must be alone on its line: the dashes have to be
+        // followed by a newline or the end of the string, not more YAML content.
+        if after_dashes.is_empty() || after_dashes.starts_with('\n') {
+            break candidate;
+        }
+        search_from = candidate + delim.len();
+    };
+
+    let after_close = yaml_end + delim.len();
+    let body_start_in_rest = match after_open[after_close..].strip_prefix('\n') {
+        Some(_) => after_close + 1,
+        None => after_close,
+    };
+
+    Some((after_open.get(1..yaml_end).unwrap_or(""), 3 + body_start_in_rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_note_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "obsidianmd-note-test-{name}-{}.md",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn checklist_items_and_toggle_round_trip_through_note() {
+        let path = temp_note_path("checklist-roundtrip");
+        fs::write(&path, "- [ ] first\n- [x] second\n").unwrap();
+
+        let note = Note::open(path.clone()).unwrap();
+        let items = note.checklist_items(None).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(!items[0].checked());
+        assert!(items[1].checked());
+
+        note.toggle(&items[0]);
+        note.save().unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(saved, "- [x] first\n- [x] second\n");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn split_frontmatter_requires_delimiter_alone_on_its_line() {
+        let contents = "---\na: 1\n----\nb: 2\n---\nbody\n";
+        let (yaml, body_start) = split_frontmatter(contents).unwrap();
+
+        assert_eq!(yaml, "a: 1\n----\nb: 2");
+        assert_eq!(&contents[body_start..], "body\n");
+    }
+
+    #[test]
+    fn split_frontmatter_returns_none_without_a_closing_delimiter() {
+        assert!(split_frontmatter("---\na: 1\nno closing fence\n").is_none());
+    }
+
+    #[test]
+    fn save_preserves_untouched_frontmatter_bytes() {
+        let path = temp_note_path("preserve-frontmatter");
+        fs::write(&path, "---\nb: 1\na: 2\n---\nbody\n").unwrap();
+
+        let note = Note::open(path.clone()).unwrap();
+        note.append("more\n", None).unwrap();
+        note.save().unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("---\nb: 1\na: 2\n---\n"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn set_frontmatter_key_resyncs_the_block_immediately() {
+        let path = temp_note_path("resync-frontmatter");
+        fs::write(&path, "hello\n").unwrap();
+
+        let note = Note::open(path.clone()).unwrap();
+        note.set_frontmatter_key("title", "Hello").unwrap();
+        note.save().unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("---\ntitle: Hello\n---\n"));
+        assert!(saved.ends_with("hello\n"));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_detects_external_modification() {
+        let path = temp_note_path("conflict");
+        fs::write(&path, "original\n").unwrap();
+
+        let note = Note::open(path.clone()).unwrap();
+        fs::write(&path, "changed on disk\n").unwrap();
+
+        let err = note.save().unwrap_err();
+        assert!(matches!(err, NoteError::Conflict { .. }));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_force_overwrites_despite_external_modification() {
+        let path = temp_note_path("conflict-force");
+        fs::write(&path, "original\n").unwrap();
+
+        let note = Note::open(path.clone()).unwrap();
+        fs::write(&path, "changed on disk\n").unwrap();
+        note.append("more\n", None).unwrap();
+
+        note.save_force().unwrap();
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(saved, "original\nmore\n");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,25 @@
+//! Postprocessor pipeline run over notes just before they're serialized during
+//! [`Vault::export`](crate::Vault::export). Note: [`Note::save`](crate::Note::save) and
+//! [`Note::save_force`](crate::Note::save_force) write the note directly and do not run
+//! registered postprocessors.
+
+use crate::frontmatter::Frontmatter;
+
+/// Mutable view over a note's body and frontmatter, handed to each registered postprocessor.
+pub struct PostprocessContext {
+    /// The note's body. Postprocessors may rewrite this in place.
+    pub body: String,
+    /// The note's frontmatter. Postprocessors may rewrite this in place.
+    pub frontmatter: Frontmatter,
+}
+
+/// Outcome of running a single postprocessor, deciding what happens next in the pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostprocessResult {
+    /// Run the next postprocessor in the pipeline.
+    Continue,
+    /// Stop running postprocessors, keeping the context as modified so far.
+    StopHere,
+    /// Drop the note entirely from the current operation (e.g. an export run).
+    Skip,
+}
@@ -8,6 +8,28 @@ use xi_rope::Rope;
 use xi_rope::{Cursor, Interval, RopeInfo};
 
 use crate::markdown as md;
+use crate::markdown::ParsedLink;
+
+/// A checkbox (`- [ ]` / `- [x]`) found by [`Section::checklist_items`], carrying its
+/// text, checked state, and the rope offset of its `[ ]`/`[x]` bracket character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChecklistItem {
+    text: String,
+    checked: bool,
+    bracket_offset: usize,
+}
+
+impl ChecklistItem {
+    /// The item's text, with the leading `- [ ]`/`- [x]` stripped.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether the item is checked.
+    pub fn checked(&self) -> bool {
+        self.checked
+    }
+}
 
 pub struct Section<'a> {
     weight: usize,
@@ -107,7 +129,84 @@ impl<'a> Section<'a> {
         self.interval.end = new_end;
     }
 
-    // TODO: Add way to list checkboxes, recuperate and toggle their state.
-    // TODO: Add way to extract all links.
+    /// Extracts all Obsidian links and embeds referenced in this section's body.
+    pub fn links(&self) -> Vec<ParsedLink> {
+        ParsedLink::scan(&self.body())
+    }
+
+    /// Scans this section's body for `- [ ]` / `- [x]` checklist items.
+    pub fn checklist_items(&self) -> Vec<ChecklistItem> {
+        let body = self.body();
+        let pat = RegexBuilder::new(r"^[ \t]*-\s\[(?P<state>[ xX])\]\s(?P<text>.*)$")
+            .case_insensitive(true)
+            .multi_line(true)
+            .build()
+            .unwrap();
+
+        pat.captures_iter(&body)
+            .map(|caps| {
+                let state = caps.name("state").unwrap();
+                ChecklistItem {
+                    text: caps.name("text").unwrap().as_str().to_string(),
+                    checked: state.as_str().eq_ignore_ascii_case("x"),
+                    bracket_offset: self.interval.start + state.start(),
+                }
+            })
+            .collect()
+    }
+
+    /// Flips a checklist item's checked state in place.
+    pub fn toggle(&mut self, item: &ChecklistItem) {
+        self.set_checked(item, !item.checked);
+    }
+
+    /// Sets a checklist item's checked state in place, rewriting just its bracket.
+    pub fn set_checked(&mut self, item: &ChecklistItem, checked: bool) {
+        let mut rope = self.rope.borrow_mut();
+        let replacement = Rope::from(if checked { "x" } else { " " });
+        rope.edit(item.bracket_offset..item.bracket_offset + 1, replacement);
+    }
+
     // TODO: Add way to extract code blocks.
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checklist_items_reports_text_and_state() {
+        let rope = RefCell::new(Rope::from("- [ ] first\n- [x] second\n"));
+        let section = Section::new(0, .., &rope);
+
+        let items = section.checklist_items();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].text(), "first");
+        assert!(!items[0].checked());
+        assert_eq!(items[1].text(), "second");
+        assert!(items[1].checked());
+    }
+
+    #[test]
+    fn set_checked_rewrites_only_the_target_bracket() {
+        let rope = RefCell::new(Rope::from("- [ ] first\n- [ ] second\n"));
+        let mut section = Section::new(0, .., &rope);
+        let items = section.checklist_items();
+
+        section.set_checked(&items[1], true);
+
+        assert_eq!(section.body(), "- [ ] first\n- [x] second\n");
+    }
+
+    #[test]
+    fn toggle_flips_checked_state() {
+        let rope = RefCell::new(Rope::from("- [x] first\n"));
+        let mut section = Section::new(0, .., &rope);
+        let items = section.checklist_items();
+
+        section.toggle(&items[0]);
+
+        assert_eq!(section.body(), "- [ ] first\n");
+    }
+}
@@ -1,10 +1,27 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use pathdiff::diff_paths;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use rayon::prelude::*;
+use regex::Regex;
 use snafu::{ensure, ResultExt, Snafu};
 
 use walkdir::WalkDir;
 
-use crate::Note;
+use crate::markdown::ParsedLink;
+use crate::postprocess::{PostprocessContext, PostprocessResult};
+use crate::{Frontmatter, FrontmatterStrategy, Note};
+
+/// Ascii set used to percent-encode relative hrefs generated by `Vault::export`: the
+/// usual controls, plus characters that break unescaped markdown link syntax.
+const EXPORT_ASCII_SET: &AsciiSet = &CONTROLS.add(b' ').add(b'(').add(b')').add(b'%');
+
+/// Maximum embed recursion depth before `Vault::render` gives up and leaves an embed untouched.
+const MAX_EMBED_DEPTH: usize = 10;
 
 #[derive(Debug, Snafu)]
 pub enum VaultError {
@@ -17,6 +34,20 @@ pub enum VaultError {
     #[snafu(display("failed to open note"))]
     OpenNote { source: crate::note::NoteError },
 
+    #[snafu(display("failed to render embedded note"))]
+    Render { source: crate::note::NoteError },
+
+    #[snafu(display("failed to export vault"))]
+    Export { source: io::Error },
+
+    #[snafu(display("failed to export note"))]
+    ExportNote { source: crate::note::NoteError },
+
+    #[snafu(display("failed to export note frontmatter"))]
+    ExportFrontmatter {
+        source: crate::frontmatter::FrontmatterError,
+    },
+
     #[snafu(display("vault does not exist"))]
     VaultDoesNotExist,
 }
@@ -29,13 +60,39 @@ pub struct Config {
     pub templates: Option<PathBuf>,
 }
 
+type Postprocessor = Box<dyn Fn(&mut PostprocessContext) -> PostprocessResult + Send>;
+
+/// In-memory cache mapping a lowercased note name to the path(s) of matching notes.
+type NoteIndex = HashMap<String, Vec<PathBuf>>;
+
 /// Struct for interacting with an obsidian vault.
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Default)]
 pub struct Vault {
     config: Config,
     path: PathBuf,
+    postprocessors: Mutex<Vec<Postprocessor>>,
+    index: Mutex<Option<NoteIndex>>,
+}
+
+impl std::fmt::Debug for Vault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Vault")
+            .field("config", &self.config)
+            .field("path", &self.path)
+            .field("postprocessors", &self.postprocessors.lock().unwrap().len())
+            .field("indexed", &self.index.lock().unwrap().is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for Vault {
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config && self.path == other.path
+    }
 }
 
+impl Eq for Vault {}
+
 impl Vault {
     /// Opens an obsidian vault.
     ///
@@ -45,15 +102,71 @@ impl Vault {
     /// Will return an error if the vault path does not exist on disk.
     pub fn open(path: PathBuf, config: Config) -> Result<Self> {
         ensure!(path.exists(), VaultDoesNotExistSnafu);
-        Ok(Self { config, path })
+        Ok(Self {
+            config,
+            path,
+            postprocessors: Mutex::new(Vec::new()),
+            index: Mutex::new(None),
+        })
+    }
+
+    /// Builds an in-memory index mapping lowercased note names to their path(s).
+    ///
+    /// The directory walk itself runs sequentially; filtering its entries and grouping
+    /// them by name run in parallel across rayon's thread pool.
+    ///
+    /// Once built, `lookup` and `daily` consult the index instead of re-walking the
+    /// vault on every call. Call this again to pick up changes made on disk.
+    ///
+    /// # Errors
+    /// Will return an error if a file in the vault is inaccessible.
+    pub fn index(&self) -> Result<()> {
+        let entries = WalkDir::new(&self.path)
+            .into_iter()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context(ListEntrySnafu)?;
+
+        let index = entries
+            .par_iter()
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                    return None;
+                }
+                let name = path.file_stem()?.to_str()?.to_lowercase();
+                Some((name, PathBuf::from(path)))
+            })
+            .fold(NoteIndex::new, |mut acc, (name, path)| {
+                acc.entry(name).or_default().push(path);
+                acc
+            })
+            .reduce(NoteIndex::new, |mut a, b| {
+                for (name, mut paths) in b {
+                    a.entry(name).or_default().append(&mut paths);
+                }
+                a
+            });
+
+        *self.index.lock().unwrap() = Some(index);
+        Ok(())
     }
 
     /// Lookup a note by name in the vault, returning its path.
     ///
+    /// Consults the cached index built by `Vault::index`, if any, instead of walking
+    /// the vault.
+    ///
     /// # Errors
     /// Will return an error if a file in the vault is inaccessible.
     pub fn lookup(&self, note_name: &str) -> Result<Vec<PathBuf>> {
-        // TODO: Cache?
+        if let Some(index) = self.index.lock().unwrap().as_ref() {
+            return Ok(index
+                .get(&note_name.to_lowercase())
+                .cloned()
+                .unwrap_or_default());
+        }
+
         let mut buf = Vec::new();
 
         for entry in WalkDir::new(&self.path).into_iter() {
@@ -69,6 +182,58 @@ impl Vault {
         Ok(buf)
     }
 
+    /// Collects the path of every markdown note in the vault, consulting the cached
+    /// index built by `Vault::index`, if any.
+    fn all_note_paths(&self) -> Result<Vec<PathBuf>> {
+        if let Some(index) = self.index.lock().unwrap().as_ref() {
+            return Ok(index.values().flatten().cloned().collect());
+        }
+
+        WalkDir::new(&self.path)
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Ok(entry) => {
+                    let path = entry.path();
+                    let is_md = path.extension().and_then(|ext| ext.to_str()) == Some("md");
+                    (entry.file_type().is_file() && is_md).then(|| Ok(PathBuf::from(path)))
+                }
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context(ListEntrySnafu)
+    }
+
+    /// Opens and processes every note in the vault in parallel, collecting the results.
+    ///
+    /// # Errors
+    /// Will return an error if a file in the vault is inaccessible, or if any note
+    /// fails to open.
+    pub fn par_map_notes<F, T>(&self, f: F) -> Result<Vec<T>>
+    where
+        F: Fn(Note) -> T + Sync + Send,
+        T: Send,
+    {
+        self.all_note_paths()?
+            .into_par_iter()
+            .map(|path| {
+                let relative = path.strip_prefix(&self.path).unwrap_or(&path);
+                self.note(relative).map(&f)
+            })
+            .collect()
+    }
+
+    /// Opens and processes every note in the vault in parallel.
+    ///
+    /// # Errors
+    /// Will return an error if a file in the vault is inaccessible, or if any note
+    /// fails to open.
+    pub fn for_each_note<F>(&self, f: F) -> Result<()>
+    where
+        F: Fn(Note) + Sync + Send,
+    {
+        self.par_map_notes(|note| f(note)).map(|_| ())
+    }
+
     /// Get a note by its path relative to the root of the vault.
     ///
     /// # Errors
@@ -97,4 +262,537 @@ impl Vault {
             }
         }
     }
+
+    /// Registers a postprocessor to run over every note just before it's serialized by
+    /// [`Vault::export`](Vault::export).
+    ///
+    /// Postprocessors run in registration order. They are not consulted by
+    /// [`Note::save`](crate::Note::save) / `save_force`, which write notes directly.
+    pub fn add_postprocessor(
+        &self,
+        postprocessor: Box<dyn Fn(&mut PostprocessContext) -> PostprocessResult + Send>,
+    ) {
+        self.postprocessors.lock().unwrap().push(postprocessor);
+    }
+
+    /// Runs all registered postprocessors over `note`, in registration order.
+    ///
+    /// Returns the resulting body and frontmatter, or `None` if a postprocessor
+    /// requested that the note be skipped.
+    ///
+    /// # Errors
+    /// Will return an error if the note's body can't be read.
+    pub fn postprocess(&self, note: &Note) -> Result<Option<PostprocessContext>> {
+        let mut ctx = PostprocessContext {
+            body: note.body(None).context(RenderSnafu)?,
+            frontmatter: note.frontmatter().clone(),
+        };
+
+        for postprocessor in self.postprocessors.lock().unwrap().iter() {
+            match postprocessor(&mut ctx) {
+                PostprocessResult::Continue => {}
+                PostprocessResult::StopHere => break,
+                PostprocessResult::Skip => return Ok(None),
+            }
+        }
+
+        Ok(Some(ctx))
+    }
+
+    /// Renders a note to a fully-flattened markdown string, recursively expanding
+    /// `![[Other Note]]` / `![[Other Note#Heading]]` embeds inline.
+    ///
+    /// Circular embeds and chains deeper than 10 levels are left untouched rather than
+    /// erroring.
+    ///
+    /// # Errors
+    /// Will return an error if a file in the vault is inaccessible.
+    pub fn render(&self, note: &Note) -> Result<String> {
+        let body = note.body(None).context(RenderSnafu)?;
+        let mut file_tree = vec![note.path().to_path_buf()];
+        self.render_embeds(&body, &mut file_tree)
+    }
+
+    fn render_embeds(&self, body: &str, file_tree: &mut Vec<PathBuf>) -> Result<String> {
+        let embed_pat = Regex::new(r"!\[\[(?P<inner>[^\[\]]+)\]\]").unwrap();
+
+        let mut rendered = String::with_capacity(body.len());
+        let mut last_end = 0;
+
+        for caps in embed_pat.captures_iter(body) {
+            let whole = caps.get(0).unwrap();
+            rendered.push_str(&body[last_end..whole.start()]);
+            last_end = whole.end();
+
+            let inner = caps.name("inner").unwrap().as_str();
+            rendered.push_str(&match ParsedLink::parse(true, inner) {
+                Some(link) => self.resolve_embed(whole.as_str(), &link, file_tree)?,
+                None => whole.as_str().to_string(),
+            });
+        }
+        rendered.push_str(&body[last_end..]);
+
+        Ok(rendered)
+    }
+
+    fn resolve_embed(
+        &self,
+        raw: &str,
+        link: &ParsedLink,
+        file_tree: &mut Vec<PathBuf>,
+    ) -> Result<String> {
+        // `file_tree` always already contains the root note being rendered, so the
+        // chain has expanded `file_tree.len() - 1` embed levels at this point; only
+        // cap once that count would exceed `MAX_EMBED_DEPTH`.
+        if file_tree.len() > MAX_EMBED_DEPTH {
+            return Ok(raw.to_string());
+        }
+
+        let target = match self.lookup(&link.file)?.into_iter().next() {
+            Some(path) => path,
+            None => return Ok(raw.to_string()),
+        };
+
+        if file_tree.contains(&target) {
+            // Circular embed: leave the marker untouched instead of recursing forever.
+            return Ok(raw.to_string());
+        }
+
+        let relative = target.strip_prefix(&self.path).unwrap_or(&target);
+        let note = self.note(relative).context(RenderSnafu)?;
+
+        let body = match link.block.as_deref() {
+            Some(block_id) if block_id.starts_with('^') => {
+                let full_body = note.body(None).context(RenderSnafu)?;
+                find_block(&full_body, &block_id[1..]).unwrap_or(full_body)
+            }
+            Some(heading) => match note.body(Some(heading)) {
+                Ok(body) => body,
+                Err(_) => return Ok(raw.to_string()),
+            },
+            None => note.body(None).context(RenderSnafu)?,
+        };
+
+        file_tree.push(target);
+        let expanded = self.render_embeds(&body, file_tree)?;
+        file_tree.pop();
+
+        // The embed marker's own line ending follows it in the embedding note, so drop
+        // the embedded body's trailing newline to keep nested embeds from stacking them.
+        Ok(expanded.strip_suffix('\n').unwrap_or(&expanded).to_string())
+    }
+
+    /// Exports the vault to `destination` as portable, standard CommonMark.
+    ///
+    /// Embeds are resolved via `render`, `[[Note|Alias]]` links become relative
+    /// `[Alias](path/to/note.md)` links, `#Heading` anchors are slugified, and
+    /// non-markdown attachments referenced by embeds are copied alongside. Use
+    /// `frontmatter_strategy` to decide whether exported notes keep their YAML header.
+    ///
+    /// # Errors
+    /// Will return an error if a file in the vault is inaccessible, or if writing to
+    /// `destination` fails.
+    pub fn export(
+        &self,
+        destination: impl AsRef<Path>,
+        frontmatter_strategy: FrontmatterStrategy,
+    ) -> Result<()> {
+        let destination = destination.as_ref();
+        fs::create_dir_all(destination).context(ExportSnafu)?;
+
+        let attachment_index = self.build_attachment_index()?;
+        let mut attachments = HashSet::new();
+        let mut rendered_notes: HashMap<PathBuf, Option<(String, Frontmatter)>> = HashMap::new();
+
+        for note_path in self.all_note_paths()? {
+            let relative = note_path
+                .strip_prefix(&self.path)
+                .unwrap_or(&note_path)
+                .to_path_buf();
+            let note = self.note(&relative).context(ExportNoteSnafu)?;
+
+            let ctx = match self.postprocess(&note)? {
+                Some(ctx) => ctx,
+                None => {
+                    // A postprocessor asked to skip this note: omit it, and any
+                    // attachment only it embeds, from the export entirely.
+                    rendered_notes.insert(note_path, None);
+                    continue;
+                }
+            };
+
+            let mut file_tree = vec![note_path.clone()];
+            let rendered = self.render_embeds(&ctx.body, &mut file_tree)?;
+
+            for link in ParsedLink::scan(&rendered) {
+                if link.embed {
+                    if let Some(target) = attachment_index.get(&link.file.to_lowercase()) {
+                        attachments.insert(target.clone());
+                    }
+                }
+            }
+
+            rendered_notes.insert(note_path, Some((rendered, ctx.frontmatter)));
+        }
+
+        for entry in WalkDir::new(&self.path) {
+            let entry = entry.context(ListEntrySnafu)?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let path = PathBuf::from(entry.path());
+            let relative = path.strip_prefix(&self.path).unwrap_or(&path);
+            let out_path = destination.join(relative);
+            let is_md = path.extension().and_then(|ext| ext.to_str()) == Some("md");
+
+            if is_md {
+                if let Some(Some((rendered, frontmatter))) = rendered_notes.get(&path) {
+                    self.write_exported_note(
+                        &path,
+                        &out_path,
+                        rendered,
+                        frontmatter,
+                        frontmatter_strategy,
+                        &attachment_index,
+                    )?;
+                }
+            } else if attachments.contains(&path) {
+                if let Some(parent) = out_path.parent() {
+                    fs::create_dir_all(parent).context(ExportSnafu)?;
+                }
+                fs::copy(&path, &out_path).context(ExportSnafu)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Walks the vault once, indexing every file by its lowercased file name, so
+    /// attachment lookups during export don't each re-walk the whole vault.
+    fn build_attachment_index(&self) -> Result<HashMap<String, PathBuf>> {
+        let mut index = HashMap::new();
+        for entry in WalkDir::new(&self.path) {
+            let entry = entry.context(ListEntrySnafu)?;
+            if entry.file_type().is_file() {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                index.insert(name, PathBuf::from(entry.path()));
+            }
+        }
+        Ok(index)
+    }
+
+    fn write_exported_note(
+        &self,
+        note_path: &Path,
+        out_path: &Path,
+        rendered: &str,
+        frontmatter: &Frontmatter,
+        frontmatter_strategy: FrontmatterStrategy,
+        attachment_index: &HashMap<String, PathBuf>,
+    ) -> Result<()> {
+        let note_dir = note_path.parent().unwrap_or(&self.path);
+        let body = self.rewrite_links(rendered, note_dir, attachment_index)?;
+
+        let keep_frontmatter = match frontmatter_strategy {
+            FrontmatterStrategy::Never => false,
+            FrontmatterStrategy::Always => true,
+            FrontmatterStrategy::Auto => !frontmatter.is_empty(),
+        };
+
+        let mut output = String::new();
+        if keep_frontmatter {
+            let yaml = frontmatter.to_yaml().context(ExportFrontmatterSnafu)?;
+            output.push_str("---\n");
+            output.push_str(&yaml);
+            output.push_str("---\n");
+        }
+        output.push_str(&body);
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent).context(ExportSnafu)?;
+        }
+        fs::write(out_path, output).context(ExportSnafu)
+    }
+
+    /// Rewrites every remaining `[[...]]` / `![[...]]` occurrence in `body` (i.e. those
+    /// `render` didn't already inline) into portable CommonMark, relative to `note_dir`.
+    /// Links whose target isn't found in the vault are left as they are.
+    fn rewrite_links(
+        &self,
+        body: &str,
+        note_dir: &Path,
+        attachment_index: &HashMap<String, PathBuf>,
+    ) -> Result<String> {
+        let link_pat = Regex::new(r"(?P<embed>!)?\[\[(?P<inner>[^\[\]]+)\]\]").unwrap();
+
+        let mut rewritten = String::with_capacity(body.len());
+        let mut last_end = 0;
+
+        for caps in link_pat.captures_iter(body) {
+            let whole = caps.get(0).unwrap();
+            rewritten.push_str(&body[last_end..whole.start()]);
+            last_end = whole.end();
+
+            let embed = caps.name("embed").is_some();
+            let inner = caps.name("inner").unwrap().as_str();
+
+            let replacement = match ParsedLink::parse(embed, inner) {
+                Some(link) => self
+                    .rewrite_link(&link, note_dir, attachment_index)?
+                    .unwrap_or_else(|| whole.as_str().to_string()),
+                None => whole.as_str().to_string(),
+            };
+            rewritten.push_str(&replacement);
+        }
+        rewritten.push_str(&body[last_end..]);
+
+        Ok(rewritten)
+    }
+
+    fn rewrite_link(
+        &self,
+        link: &ParsedLink,
+        note_dir: &Path,
+        attachment_index: &HashMap<String, PathBuf>,
+    ) -> Result<Option<String>> {
+        let label = link.label.clone().unwrap_or_else(|| link.file.clone());
+
+        if let Some(target) = self.lookup(&link.file)?.into_iter().next() {
+            let href = relative_href(&target, note_dir);
+            let href = match link.block.as_deref() {
+                Some(heading) if !heading.starts_with('^') => {
+                    format!("{href}#{}", slugify(heading))
+                }
+                _ => href,
+            };
+            return Ok(Some(if link.embed {
+                format!("![{label}]({href})")
+            } else {
+                format!("[{label}]({href})")
+            }));
+        }
+
+        if link.embed {
+            if let Some(target) = attachment_index.get(&link.file.to_lowercase()) {
+                let href = relative_href(target, note_dir);
+                return Ok(Some(format!("![{label}]({href})")));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Computes the relative href from `from_dir` to `target`, percent-encoding it for use
+/// in a markdown link.
+fn relative_href(target: &Path, from_dir: &Path) -> String {
+    let relative = diff_paths(target, from_dir).unwrap_or_else(|| target.to_path_buf());
+    let joined = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    utf8_percent_encode(&joined, EXPORT_ASCII_SET).to_string()
+}
+
+/// Slugifies a heading into a GitHub-style fragment id.
+fn slugify(heading: &str) -> String {
+    heading
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() {
+                Some(c.to_ascii_lowercase())
+            } else if c == ' ' || c == '-' || c == '_' {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Finds the line ending in the block marker `^{id}` and returns its text, with the
+/// marker stripped.
+fn find_block(body: &str, id: &str) -> Option<String> {
+    let marker = format!("^{id}");
+    body.lines().find_map(|line| {
+        line.trim_end()
+            .strip_suffix(&marker)
+            .map(|text| text.trim_end().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory under the system temp dir, unique to this test run.
+    fn temp_vault_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("obsidianmd-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn par_map_notes_opens_notes_under_a_relative_vault_path() {
+        let base = temp_vault_dir("par-map-relative");
+        fs::write(base.join("note.md"), "hello\n").unwrap();
+
+        let relative = diff_paths(&base, std::env::current_dir().unwrap()).unwrap();
+        let vault = Vault::open(relative, Config::default()).unwrap();
+
+        let bodies = vault
+            .par_map_notes(|note| note.body(None).unwrap())
+            .unwrap();
+
+        assert_eq!(bodies, vec!["hello\n".to_string()]);
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn render_expands_nested_embeds() {
+        let base = temp_vault_dir("render-nested");
+        fs::write(base.join("a.md"), "![[b]]\n").unwrap();
+        fs::write(base.join("b.md"), "![[c]]\n").unwrap();
+        fs::write(base.join("c.md"), "leaf\n").unwrap();
+
+        let vault = Vault::open(base.clone(), Config::default()).unwrap();
+        let note = vault.note("a.md").unwrap();
+
+        assert_eq!(vault.render(&note).unwrap(), "leaf\n");
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn render_expands_nested_embeds_under_a_relative_vault_path() {
+        let base = temp_vault_dir("render-nested-relative");
+        fs::write(base.join("a.md"), "![[b]]\n").unwrap();
+        fs::write(base.join("b.md"), "![[c]]\n").unwrap();
+        fs::write(base.join("c.md"), "leaf\n").unwrap();
+
+        let relative = diff_paths(&base, std::env::current_dir().unwrap()).unwrap();
+        let vault = Vault::open(relative, Config::default()).unwrap();
+        let note = vault.note("a.md").unwrap();
+
+        assert_eq!(vault.render(&note).unwrap(), "leaf\n");
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn render_leaves_circular_embeds_untouched() {
+        let base = temp_vault_dir("render-cycle");
+        fs::write(base.join("a.md"), "![[b]]\n").unwrap();
+        fs::write(base.join("b.md"), "![[a]]\n").unwrap();
+
+        let vault = Vault::open(base.clone(), Config::default()).unwrap();
+        let note = vault.note("a.md").unwrap();
+
+        assert_eq!(vault.render(&note).unwrap(), "![[a]]\n");
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn render_stops_at_max_embed_depth() {
+        let base = temp_vault_dir("render-depth");
+        for i in 0..11 {
+            fs::write(base.join(format!("note{i}.md")), format!("![[note{}]]\n", i + 1)).unwrap();
+        }
+        fs::write(base.join("note11.md"), "leaf\n").unwrap();
+
+        let vault = Vault::open(base.clone(), Config::default()).unwrap();
+        let note = vault.note("note0.md").unwrap();
+
+        // 11 embed hops (note0 -> note11) is one past MAX_EMBED_DEPTH (10), so the
+        // last hop is left as a raw, unexpanded embed marker.
+        assert_eq!(vault.render(&note).unwrap(), "![[note11]]\n");
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn export_copies_embedded_attachments_and_flattens_embeds() {
+        let base = temp_vault_dir("export-basic");
+        fs::write(base.join("note.md"), "![[embedded]]\n![[image.png]]\n").unwrap();
+        fs::write(base.join("embedded.md"), "embedded body\n").unwrap();
+        fs::write(base.join("image.png"), b"fake-bytes").unwrap();
+        let dest = temp_vault_dir("export-basic-dest");
+
+        let vault = Vault::open(base.clone(), Config::default()).unwrap();
+        vault.export(&dest, FrontmatterStrategy::Never).unwrap();
+
+        let exported = fs::read_to_string(dest.join("note.md")).unwrap();
+        assert!(exported.contains("embedded body"));
+        assert!(exported.contains("image.png"));
+        assert!(dest.join("image.png").exists());
+
+        fs::remove_dir_all(&base).unwrap();
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn export_expands_embeds_under_a_relative_vault_path() {
+        let base = temp_vault_dir("export-relative");
+        fs::write(base.join("note.md"), "![[embedded]]\n").unwrap();
+        fs::write(base.join("embedded.md"), "embedded body\n").unwrap();
+        let dest = temp_vault_dir("export-relative-dest");
+
+        let relative = diff_paths(&base, std::env::current_dir().unwrap()).unwrap();
+        let vault = Vault::open(relative, Config::default()).unwrap();
+        vault.export(&dest, FrontmatterStrategy::Never).unwrap();
+
+        let exported = fs::read_to_string(dest.join("note.md")).unwrap();
+        assert!(exported.contains("embedded body"));
+
+        fs::remove_dir_all(&base).unwrap();
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn export_omits_notes_a_postprocessor_skips() {
+        let base = temp_vault_dir("export-skip");
+        fs::write(base.join("keep.md"), "kept\n").unwrap();
+        fs::write(base.join("drop.md"), "dropped\n").unwrap();
+        let dest = temp_vault_dir("export-skip-dest");
+
+        let vault = Vault::open(base.clone(), Config::default()).unwrap();
+        vault.add_postprocessor(Box::new(|ctx: &mut PostprocessContext| {
+            if ctx.body.trim() == "dropped" {
+                PostprocessResult::Skip
+            } else {
+                PostprocessResult::Continue
+            }
+        }));
+        vault.export(&dest, FrontmatterStrategy::Never).unwrap();
+
+        assert!(dest.join("keep.md").exists());
+        assert!(!dest.join("drop.md").exists());
+
+        fs::remove_dir_all(&base).unwrap();
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn index_then_par_map_notes_also_works_under_a_relative_vault_path() {
+        let base = temp_vault_dir("par-map-relative-indexed");
+        fs::write(base.join("note.md"), "hello\n").unwrap();
+
+        let relative = diff_paths(&base, std::env::current_dir().unwrap()).unwrap();
+        let vault = Vault::open(relative, Config::default()).unwrap();
+        vault.index().unwrap();
+
+        let bodies = vault
+            .par_map_notes(|note| note.body(None).unwrap())
+            .unwrap();
+
+        assert_eq!(bodies, vec!["hello\n".to_string()]);
+
+        fs::remove_dir_all(&base).unwrap();
+    }
 }